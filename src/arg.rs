@@ -79,14 +79,39 @@ Can be multiple, but should be overlaping"#
     -5k - smaller than 5k,
 
 Possible file size units are as follows:
-    k - kilobytes (1024 bytes)
-    M - megabytes (1024 kilobytes)
-    G - gigabytes (1024 megabytes)
-    T - terabytes (1024 gigabytes)
-    P - petabytes (1024 terabytes)"#
+    b          - bytes
+    k, ki      - kibibytes (1024 bytes)
+    M, Mi      - mebibytes (1024 kibibytes)
+    G, Gi      - gibibytes (1024 mebibytes)
+    T, Ti      - tebibytes (1024 gibibytes)
+    P, Pi      - pebibytes (1024 tebibytes)
+    kb, mb, gb, tb, pb - decimal (1000-based) equivalents"#
     )]
     pub size: Vec<FindSize>,
 
+    /// Tag key:value pair for match, can be multiple. See `TagFilter` for
+    /// how this is applied
+    #[structopt(name = "tpatern", long = "tag", raw(number_of_values = "1"))]
+    pub tag: Vec<FindTag>,
+
+    /// Limit the number of matched keys, the rest are not processed
+    #[structopt(name = "limit", long = "limit", short = "l")]
+    pub limit: Option<usize>,
+
+    /// Instead of printing the matched keys, print a single summary line
+    /// with the total count and size of the matched objects
+    #[structopt(name = "summarize", long = "summarize")]
+    pub summarize: bool,
+
+    /// Output format for -print, -ls and -lstags
+    #[structopt(
+        name = "format",
+        long = "format",
+        default_value = "text",
+        raw(possible_values = r#"&["text", "json"]"#)
+    )]
+    pub format: FindOutputFormat,
+
     //  /// Action to be ran with matched list of paths
     #[structopt(subcommand)]
     pub cmd: Option<Cmd>,
@@ -94,7 +119,12 @@ Possible file size units are as follows:
 
 #[derive(StructOpt, Debug, PartialEq, Clone)]
 pub enum Cmd {
-    /// Exec any shell program with every key
+    /// Run a utility with every matched key, {} is replaced with the
+    /// object's s3:// url. Like GNU find's -exec, the utility is run
+    /// directly (not through a shell) with `{}` substituted as a single
+    /// argument, so it is safe to run over buckets whose object keys you
+    /// don't fully trust; it does NOT support shell syntax such as pipes,
+    /// redirection or `;`
     #[structopt(name = "-exec")]
     Exec {
         /// Utility(program) to run
@@ -141,6 +171,29 @@ pub enum Cmd {
     /// Make the matched keys public available (readonly)
     #[structopt(name = "-public")]
     Public,
+
+    /// Copy matched keys to a destination bucket/prefix using S3
+    /// server-side copy, without downloading the objects
+    #[structopt(name = "-copy")]
+    Copy {
+        /// Bucket and prefix to copy matched keys to. It should be s3://bucket/path
+        #[structopt(name = "destination")]
+        destination: S3path,
+
+        /// Copy every matched key directly under the destination prefix,
+        /// instead of preserving its path relative to the search prefix
+        #[structopt(long = "flat")]
+        flat: bool,
+    },
+
+    /// Move matched keys to a destination bucket/prefix using S3
+    /// server-side copy followed by a delete of the source key
+    #[structopt(name = "-move")]
+    Move {
+        /// Bucket and prefix to move matched keys to. It should be s3://bucket/path
+        #[structopt(name = "destination")]
+        destination: S3path,
+    },
 }
 
 #[derive(Fail, Debug)]
@@ -157,6 +210,8 @@ pub enum FindError {
     TagKeyParseError,
     #[fail(display = "Cannot parse tag value")]
     TagValueParseError,
+    #[fail(display = "Invalid output format, should be one of: text, json")]
+    FormatParse,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -169,7 +224,7 @@ impl FromStr for S3path {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<S3path, Error> {
-        let s3_vec: Vec<&str> = s.split('/').collect();
+        let s3_vec: Vec<&str> = s.splitn(4, '/').collect();
         let bucket = s3_vec.get(2).unwrap_or(&"");
         let prefix = s3_vec.get(3).map(|x| x.to_owned());
 
@@ -198,23 +253,34 @@ impl FromStr for FindSize {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<FindSize, Error> {
-        let re = Regex::new(r"([+-]?)(\d*)([kMGTP]?)$")?;
+        let re = Regex::new(r"([+-]?)(\d*)([a-zA-Z]*)$")?;
         let m = re.captures(s).unwrap();
 
         let sign = m.get(1).unwrap().as_str().chars().next();
         let number: i64 = m.get(2).unwrap().as_str().parse()?;
-        let metric = m.get(3).unwrap().as_str().chars().next();
-
-        let bytes = match metric {
-            None => number,
-            Some('k') => number * 1024,
-            Some('M') => number * 1024_i64.pow(2),
-            Some('G') => number * 1024_i64.pow(3),
-            Some('T') => number * 1024_i64.pow(4),
-            Some('P') => number * 1024_i64.pow(5),
-            Some(_) => return Err(FindError::SizeParse.into()),
+        let unit = m.get(3).unwrap().as_str();
+
+        // A bare letter (`k`, `M`, ...) or an explicit `i`-suffixed unit
+        // (`ki`, `Mi`, ...) is binary (1024^n), matching `fd`'s size
+        // filter; a lowercase decimal unit (`kb`, `mb`, ...) is 1000^n,
+        // and `b` is a plain byte count.
+        let multiplier = match unit {
+            "" | "b" => 1,
+            "k" | "ki" => 1024,
+            "kb" => 1000,
+            "M" | "Mi" => 1024_i64.pow(2),
+            "mb" => 1000_i64.pow(2),
+            "G" | "Gi" => 1024_i64.pow(3),
+            "gb" => 1000_i64.pow(3),
+            "T" | "Ti" => 1024_i64.pow(4),
+            "tb" => 1000_i64.pow(4),
+            "P" | "Pi" => 1024_i64.pow(5),
+            "pb" => 1000_i64.pow(5),
+            _ => return Err(FindError::SizeParse.into()),
         };
 
+        let bytes = number * multiplier;
+
         match sign {
             Some('+') => Ok(FindSize::Bigger(bytes)),
             Some('-') => Ok(FindSize::Lower(bytes)),
@@ -297,6 +363,27 @@ impl FromStr for FindTag {
     }
 }
 
+/// Output format for commands that print matched keys (`-print`, `-ls`,
+/// `-lstags`): `text` keeps the current human-readable output, `json`
+/// emits one JSON object per line so the output composes with `jq`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FindOutputFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for FindOutputFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<FindOutputFormat, Error> {
+        match s {
+            "text" => Ok(FindOutputFormat::Text),
+            "json" => Ok(FindOutputFormat::Json),
+            _ => Err(FindError::FormatParse.into()),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -325,6 +412,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn s3path_correct_multi_segment_prefix() {
+        let url = "s3://testbucket/archive/2024/q1";
+        let path: S3path = url.parse().unwrap();
+        assert_eq!(path.bucket, "testbucket", "This should be 'testbucket'");
+        assert_eq!(
+            path.prefix,
+            Some("archive/2024/q1".to_string()),
+            "This should keep the full remainder of the path, not just the first segment"
+        );
+    }
+
     #[test]
     fn s3path_correct_short() {
         let url = "s3://testbucket";
@@ -423,6 +522,58 @@ mod tests {
         );
     }
 
+    #[test]
+    fn size_corect_ki() {
+        let size_str = "1111ki";
+        let size = size_str.parse::<FindSize>();
+
+        assert_eq!(
+            size.ok(),
+            Some(FindSize::Equal(1111 * 1024)),
+            "ki should be binary, same as k"
+        );
+    }
+
+    #[test]
+    fn size_corect_kb() {
+        let size_str = "1111kb";
+        let size = size_str.parse::<FindSize>();
+
+        assert_eq!(
+            size.ok(),
+            Some(FindSize::Equal(1111 * 1000)),
+            "kb should be decimal"
+        );
+    }
+
+    #[test]
+    fn size_corect_mb() {
+        let size_str = "+2mb";
+        let size = size_str.parse::<FindSize>();
+
+        assert_eq!(
+            size.ok(),
+            Some(FindSize::Bigger(2 * 1000 * 1000)),
+            "mb should be decimal"
+        );
+    }
+
+    #[test]
+    fn size_corect_b() {
+        let size_str = "-100b";
+        let size = size_str.parse::<FindSize>();
+
+        assert_eq!(size.ok(), Some(FindSize::Lower(100)), "b should be bytes");
+    }
+
+    #[test]
+    fn size_incorect_unit() {
+        let size_str = "100zz";
+        let size = size_str.parse::<FindSize>();
+
+        assert!(size.is_err(), "Should be error");
+    }
+
     #[test]
     fn size_incorect_negative() {
         let size_str = "-";
@@ -551,4 +702,22 @@ mod tests {
         let time = str.parse::<FindTag>();
         assert!(time.is_err(), "Should not be parsed");
     }
+
+    #[test]
+    fn format_text() {
+        let format = "text".parse::<FindOutputFormat>();
+        assert_eq!(format.ok(), Some(FindOutputFormat::Text));
+    }
+
+    #[test]
+    fn format_json() {
+        let format = "json".parse::<FindOutputFormat>();
+        assert_eq!(format.ok(), Some(FindOutputFormat::Json));
+    }
+
+    #[test]
+    fn format_incorect() {
+        let format = "yaml".parse::<FindOutputFormat>();
+        assert!(format.is_err(), "Should not be parsed");
+    }
 }
@@ -0,0 +1,272 @@
+use chrono::{DateTime, Utc};
+use rusoto_s3::Object;
+
+use crate::arg::{FindOpt, FindSize, FindTag, FindTime, InameGlob};
+
+/// A single match criterion that can be tested against a listed S3 object.
+pub trait Filter {
+    fn filter(&self, object: &Object) -> bool;
+}
+
+/// An ordered collection of filters. An object is a match only if every
+/// filter in the list passes (AND semantics).
+pub struct FilterList(pub Vec<Box<dyn Filter>>);
+
+impl FilterList {
+    pub fn test_match(&self, object: &Object) -> bool {
+        self.0.iter().all(|filter| filter.filter(object))
+    }
+}
+
+impl From<&FindOpt> for FilterList {
+    fn from(opt: &FindOpt) -> FilterList {
+        let mut list: Vec<Box<dyn Filter>> = Vec::new();
+
+        for name in &opt.name {
+            list.push(Box::new(NameFilter(name.clone())));
+        }
+
+        for iname in &opt.iname {
+            list.push(Box::new(InameFilter(iname.clone())));
+        }
+
+        for regex in &opt.regex {
+            list.push(Box::new(RegexFilter(regex.clone())));
+        }
+
+        for size in &opt.size {
+            list.push(Box::new(SizeFilter(size.clone())));
+        }
+
+        for mtime in &opt.mtime {
+            list.push(Box::new(MtimeFilter(mtime.clone())));
+        }
+
+        FilterList(list)
+    }
+}
+
+pub struct NameFilter(pub glob::Pattern);
+
+impl Filter for NameFilter {
+    fn filter(&self, object: &Object) -> bool {
+        match &object.key {
+            Some(key) => self.0.matches(key),
+            None => false,
+        }
+    }
+}
+
+pub struct InameFilter(pub InameGlob);
+
+impl Filter for InameFilter {
+    fn filter(&self, object: &Object) -> bool {
+        let options = glob::MatchOptions {
+            case_sensitive: false,
+            ..Default::default()
+        };
+
+        match &object.key {
+            Some(key) => self.0 .0.matches_with(key, options),
+            None => false,
+        }
+    }
+}
+
+pub struct RegexFilter(pub regex::Regex);
+
+impl Filter for RegexFilter {
+    fn filter(&self, object: &Object) -> bool {
+        match &object.key {
+            Some(key) => self.0.is_match(key),
+            None => false,
+        }
+    }
+}
+
+pub struct SizeFilter(pub FindSize);
+
+impl Filter for SizeFilter {
+    fn filter(&self, object: &Object) -> bool {
+        let size = object.size.unwrap_or(0);
+        match &self.0 {
+            FindSize::Bigger(s) => size > *s,
+            FindSize::Lower(s) => size < *s,
+            FindSize::Equal(s) => size == *s,
+        }
+    }
+}
+
+pub struct MtimeFilter(pub FindTime);
+
+impl Filter for MtimeFilter {
+    fn filter(&self, object: &Object) -> bool {
+        let last_modified = match &object.last_modified {
+            Some(s) => match DateTime::parse_from_rfc3339(s) {
+                Ok(t) => t.with_timezone(&Utc),
+                Err(_) => return false,
+            },
+            None => return false,
+        };
+
+        let now = Utc::now();
+        let age = now.signed_duration_since(last_modified).num_seconds();
+
+        // `+5d` (`Upper`) means "from now-5d to now", i.e. modified within
+        // the last 5 days; `-5d` (`Lower`) means "before now-5d", i.e.
+        // older than that, per the `--mtime` help text in `arg.rs`.
+        match &self.0 {
+            FindTime::Upper(s) => age <= *s,
+            FindTime::Lower(s) => age >= *s,
+        }
+    }
+}
+
+/// `ListObjectsV2` never returns tags, so tag matching cannot be folded into
+/// `FilterList`: it requires a `GetObjectTagging` call per candidate key.
+/// Callers should run this only after the cheaper `FilterList` filters have
+/// already narrowed the candidates down, to keep the number of extra API
+/// calls to a minimum.
+pub struct TagFilter(pub Vec<FindTag>);
+
+impl TagFilter {
+    /// Returns true if `object_tags` contains every requested key:value pair.
+    pub fn matches(&self, object_tags: &[rusoto_s3::Tag]) -> bool {
+        self.0.iter().all(|tag| {
+            object_tags
+                .iter()
+                .any(|t| t.key == tag.key && t.value == tag.value)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object_with_key(key: &str) -> Object {
+        Object {
+            key: Some(key.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn object_with_age(seconds_old: i64) -> Object {
+        let last_modified = Utc::now() - chrono::Duration::seconds(seconds_old);
+        Object {
+            last_modified: Some(last_modified.to_rfc3339()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn mtime_upper_matches_recent_objects() {
+        // `+1h`: modified within the last hour.
+        let filter = MtimeFilter(FindTime::Upper(3600));
+        assert!(filter.filter(&object_with_age(60)));
+        assert!(!filter.filter(&object_with_age(7200)));
+    }
+
+    #[test]
+    fn mtime_lower_matches_older_objects() {
+        // `-1h`: modified more than an hour ago.
+        let filter = MtimeFilter(FindTime::Lower(3600));
+        assert!(filter.filter(&object_with_age(7200)));
+        assert!(!filter.filter(&object_with_age(60)));
+    }
+
+    #[test]
+    fn name_filter_match() {
+        let filter = NameFilter(glob::Pattern::new("*.txt").unwrap());
+        assert!(filter.filter(&object_with_key("file.txt")));
+        assert!(!filter.filter(&object_with_key("file.csv")));
+    }
+
+    #[test]
+    fn iname_filter_match() {
+        let filter = InameFilter(InameGlob(glob::Pattern::new("*.txt").unwrap()));
+        assert!(filter.filter(&object_with_key("FILE.TXT")));
+        assert!(!filter.filter(&object_with_key("file.csv")));
+    }
+
+    #[test]
+    fn iname_filter_matches_with_upper_case_pattern() {
+        let filter = InameFilter(InameGlob(glob::Pattern::new("*.JPG").unwrap()));
+        assert!(filter.filter(&object_with_key("photo.jpg")));
+        assert!(filter.filter(&object_with_key("PHOTO.JPG")));
+    }
+
+    #[test]
+    fn regex_filter_match() {
+        let filter = RegexFilter(regex::Regex::new(r"^file\d+\.txt$").unwrap());
+        assert!(filter.filter(&object_with_key("file42.txt")));
+        assert!(!filter.filter(&object_with_key("file.txt")));
+    }
+
+    #[test]
+    fn size_filter_match() {
+        let object = Object {
+            size: Some(2048),
+            ..Default::default()
+        };
+
+        assert!(SizeFilter(FindSize::Equal(2048)).filter(&object));
+        assert!(SizeFilter(FindSize::Bigger(1024)).filter(&object));
+        assert!(SizeFilter(FindSize::Lower(4096)).filter(&object));
+        assert!(!SizeFilter(FindSize::Lower(1024)).filter(&object));
+    }
+
+    #[test]
+    fn filter_list_requires_all_filters() {
+        let list = FilterList(vec![
+            Box::new(NameFilter(glob::Pattern::new("*.txt").unwrap())),
+            Box::new(SizeFilter(FindSize::Bigger(10))),
+        ]);
+
+        let matching = Object {
+            key: Some("file.txt".to_string()),
+            size: Some(20),
+            ..Default::default()
+        };
+        let non_matching = Object {
+            key: Some("file.txt".to_string()),
+            size: Some(5),
+            ..Default::default()
+        };
+
+        assert!(list.test_match(&matching));
+        assert!(!list.test_match(&non_matching));
+    }
+
+    #[test]
+    fn tag_filter_requires_all_tags() {
+        let filter = TagFilter(vec![
+            FindTag {
+                key: "stage".to_string(),
+                value: "tmp".to_string(),
+            },
+            FindTag {
+                key: "owner".to_string(),
+                value: "infra".to_string(),
+            },
+        ]);
+
+        let full_match = vec![
+            rusoto_s3::Tag {
+                key: "stage".to_string(),
+                value: "tmp".to_string(),
+            },
+            rusoto_s3::Tag {
+                key: "owner".to_string(),
+                value: "infra".to_string(),
+            },
+        ];
+        let partial_match = vec![rusoto_s3::Tag {
+            key: "stage".to_string(),
+            value: "tmp".to_string(),
+        }];
+
+        assert!(filter.matches(&full_match));
+        assert!(!filter.matches(&partial_match));
+    }
+}
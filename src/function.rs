@@ -0,0 +1,598 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+use failure::Error;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use rusoto_core::{HttpClient, Region};
+use rusoto_credential::StaticProvider;
+use rusoto_s3::{
+    CopyObjectRequest, DeleteObjectRequest, GetObjectRequest, GetObjectTaggingRequest,
+    ListObjectsV2Request, Object, PutObjectAclRequest, PutObjectTaggingRequest, S3Client,
+    Tag as S3Tag, Tagging, S3,
+};
+
+use crate::arg::{Cmd, FindOpt, FindOutputFormat, FindTag, S3path};
+use crate::filter::{FilterList, TagFilter};
+use crate::output::FindObject;
+use crate::stat::FindStat;
+
+/// `Object.size` is an `Option<i64>`; S3 never reports a negative size, but
+/// fold it into `FindStat`'s `u64` defensively rather than panicking on an
+/// unexpected value.
+fn object_size(object: &Object) -> u64 {
+    object.size.unwrap_or(0).max(0) as u64
+}
+
+/// Whether `--limit` has already been hit, checked before a match is
+/// processed so `--limit 0` processes nothing (rather than after, which
+/// would let exactly one match through regardless of the limit).
+fn limit_reached(matched: usize, limit: Option<usize>) -> bool {
+    matches!(limit, Some(limit) if matched >= limit)
+}
+
+/// Builds the argv for `-exec`: splits `utility` on whitespace and replaces
+/// any `{}` word with `url`. Like GNU find's `-exec`, the program is run
+/// directly rather than through a shell, so a key containing shell
+/// metacharacters (backticks, `;`, `$( )`, ...) can't be used to inject
+/// commands. Returns `None` if `utility` is empty.
+fn exec_argv<'a>(utility: &'a str, url: &'a str) -> Option<Vec<&'a str>> {
+    let argv: Vec<&str> = utility
+        .split_whitespace()
+        .map(|word| if word == "{}" { url } else { word })
+        .collect();
+
+    if argv.is_empty() {
+        None
+    } else {
+        Some(argv)
+    }
+}
+
+/// Joins `destination` with the (bucket-controlled) S3 key, keeping only the
+/// key's `Normal` path components. This drops a leading `/` and any `..`, so
+/// a malicious key like `../../../home/user/.ssh/authorized_keys` can't
+/// write `-download` output outside of `destination`.
+fn safe_join(destination: &str, key: &str) -> PathBuf {
+    let mut target = PathBuf::from(destination);
+
+    for component in Path::new(key).components() {
+        if let Component::Normal(part) = component {
+            target.push(part);
+        }
+    }
+
+    target
+}
+
+/// Drives the walk over a bucket/prefix: pages through `ListObjectsV2`,
+/// keeps only the objects that pass the configured filters, and runs the
+/// requested `Cmd` against each match.
+pub struct Find {
+    client: S3Client,
+    opts: FindOpt,
+    filters: FilterList,
+}
+
+impl Find {
+    pub fn new(opts: FindOpt) -> Find {
+        let client = build_client(&opts);
+        let filters = FilterList::from(&opts);
+
+        Find {
+            client,
+            opts,
+            filters,
+        }
+    }
+
+    /// Walks the bucket/prefix, stopping once `--limit` matches have been
+    /// found (when set), without running `process` on the match that would
+    /// put the count over the limit. With `--summarize` (which requires no
+    /// action subcommand), matches are folded into a `FindStat` instead of
+    /// being handed to `process`, and a single totals line is printed once
+    /// the walk ends.
+    pub fn run(&self) -> Result<(), Error> {
+        if self.opts.summarize && self.opts.cmd.is_some() {
+            return Err(failure::err_msg(
+                "--summarize cannot be combined with an action subcommand",
+            ));
+        }
+
+        let mut matched = 0usize;
+        let mut stat = FindStat::default();
+        let mut continuation_token = None;
+
+        'walk: loop {
+            let request = ListObjectsV2Request {
+                bucket: self.opts.path.bucket.clone(),
+                prefix: self.opts.path.prefix.clone(),
+                continuation_token: continuation_token.clone(),
+                ..Default::default()
+            };
+
+            let response = self.client.list_objects_v2(request).sync()?;
+            let contents = response.contents.unwrap_or_default();
+            let is_last_page = response.next_continuation_token.is_none();
+            continuation_token = response.next_continuation_token;
+
+            for object in &contents {
+                if !self.filters.test_match(object) {
+                    continue;
+                }
+
+                if limit_reached(matched, self.opts.limit) {
+                    break 'walk;
+                }
+
+                let cached_tags = match self.tags_for_processing(object)? {
+                    Some(tags) => tags,
+                    None => continue,
+                };
+
+                if self.opts.summarize {
+                    stat = stat + FindStat::new(object_size(object));
+                } else {
+                    self.process(object, cached_tags)?;
+                }
+
+                matched += 1;
+            }
+
+            if is_last_page {
+                break;
+            }
+        }
+
+        if self.opts.summarize {
+            println!("{}", stat);
+        }
+
+        Ok(())
+    }
+
+    fn process(&self, object: &Object, cached_tags: Option<Vec<S3Tag>>) -> Result<(), Error> {
+        match &self.opts.cmd {
+            None | Some(Cmd::Ls) => self.print_object(object, false),
+            Some(Cmd::Print) => self.print_object(object, true),
+            Some(Cmd::LsTags) => self.print_object_with_tags(object, cached_tags),
+            Some(Cmd::Delete) => self.delete(object),
+            Some(Cmd::Download { force, destination }) => {
+                self.download(object, destination, *force)
+            }
+            Some(Cmd::Exec { utility }) => self.exec(object, utility),
+            Some(Cmd::Tags { tags }) => self.set_tags(object, tags),
+            Some(Cmd::Public) => self.make_public(object),
+            Some(Cmd::Copy { destination, flat }) => self.copy(object, destination, *flat),
+            Some(Cmd::Move { destination }) => self.move_object(object, destination),
+        }
+    }
+
+    /// `detailed` (used by `-print`) adds the etag, size, last-modified time
+    /// and storage class around the url in text output.
+    fn print_object(&self, object: &Object, detailed: bool) -> Result<(), Error> {
+        let key = match &object.key {
+            Some(key) => key,
+            None => return Ok(()),
+        };
+
+        match self.opts.format {
+            FindOutputFormat::Text if detailed => println!(
+                "{} {} {} {} {}",
+                object.e_tag.as_deref().unwrap_or(""),
+                object.size.unwrap_or(0),
+                object.last_modified.as_deref().unwrap_or(""),
+                self.url_for(key),
+                object.storage_class.as_deref().unwrap_or("")
+            ),
+            FindOutputFormat::Text => println!("{}", self.url_for(key)),
+            FindOutputFormat::Json => println!("{}", FindObject::new(object, None).to_json_line()?),
+        }
+
+        Ok(())
+    }
+
+    fn print_object_with_tags(
+        &self,
+        object: &Object,
+        cached_tags: Option<Vec<S3Tag>>,
+    ) -> Result<(), Error> {
+        let key = match &object.key {
+            Some(key) => key,
+            None => return Ok(()),
+        };
+
+        let tags = match cached_tags {
+            Some(tags) => tags,
+            None => self.fetch_tags(key)?,
+        };
+
+        match self.opts.format {
+            FindOutputFormat::Text => {
+                let tags = tags
+                    .iter()
+                    .map(|tag| format!("{}:{}", tag.key, tag.value))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                println!("{}\t{}", self.url_for(key), tags);
+            }
+            FindOutputFormat::Json => {
+                println!("{}", FindObject::new(object, Some(&tags)).to_json_line()?)
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Applies `--tag` as a lazy second pass (see `TagFilter`) and hands the
+    /// fetched tags back so a later `-lstags` doesn't issue a second
+    /// `GetObjectTagging` call for the same key. `Ok(None)` means the object
+    /// should be skipped; otherwise the inner `Option` is the already-fetched
+    /// tag set, or `None` if `--tag` wasn't set (nothing was fetched).
+    fn tags_for_processing(&self, object: &Object) -> Result<Option<Option<Vec<S3Tag>>>, Error> {
+        if self.opts.tag.is_empty() {
+            return Ok(Some(None));
+        }
+
+        let key = match &object.key {
+            Some(key) => key,
+            None => return Ok(None),
+        };
+
+        let tags = self.fetch_tags(key)?;
+        if TagFilter(self.opts.tag.clone()).matches(&tags) {
+            Ok(Some(Some(tags)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn fetch_tags(&self, key: &str) -> Result<Vec<S3Tag>, Error> {
+        let response = self
+            .client
+            .get_object_tagging(GetObjectTaggingRequest {
+                bucket: self.opts.path.bucket.clone(),
+                key: key.to_string(),
+                ..Default::default()
+            })
+            .sync()?;
+
+        Ok(response.tag_set)
+    }
+
+    fn delete(&self, object: &Object) -> Result<(), Error> {
+        let key = match &object.key {
+            Some(key) => key,
+            None => return Ok(()),
+        };
+
+        self.client
+            .delete_object(DeleteObjectRequest {
+                bucket: self.opts.path.bucket.clone(),
+                key: key.clone(),
+                ..Default::default()
+            })
+            .sync()?;
+
+        Ok(())
+    }
+
+    fn download(&self, object: &Object, destination: &str, force: bool) -> Result<(), Error> {
+        let key = match &object.key {
+            Some(key) => key,
+            None => return Ok(()),
+        };
+
+        let target = safe_join(destination, key);
+        if target.exists() && !force {
+            return Ok(());
+        }
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let response = self
+            .client
+            .get_object(GetObjectRequest {
+                bucket: self.opts.path.bucket.clone(),
+                key: key.clone(),
+                ..Default::default()
+            })
+            .sync()?;
+
+        let body = response
+            .body
+            .ok_or_else(|| failure::err_msg("object has no body"))?;
+
+        let mut buffer = Vec::new();
+        body.into_blocking_read().read_to_end(&mut buffer)?;
+
+        let mut file = fs::File::create(&target)?;
+        file.write_all(&buffer)?;
+
+        Ok(())
+    }
+
+    fn exec(&self, object: &Object, utility: &str) -> Result<(), Error> {
+        let key = match &object.key {
+            Some(key) => key,
+            None => return Ok(()),
+        };
+
+        let url = self.url_for(key);
+        let program_and_args = match exec_argv(utility, &url) {
+            Some(program_and_args) => program_and_args,
+            None => return Ok(()),
+        };
+
+        Command::new(program_and_args[0])
+            .args(&program_and_args[1..])
+            .status()?;
+
+        Ok(())
+    }
+
+    fn set_tags(&self, object: &Object, tags: &[FindTag]) -> Result<(), Error> {
+        let key = match &object.key {
+            Some(key) => key,
+            None => return Ok(()),
+        };
+
+        let tag_set = tags
+            .iter()
+            .map(|tag| S3Tag {
+                key: tag.key.clone(),
+                value: tag.value.clone(),
+            })
+            .collect();
+
+        self.client
+            .put_object_tagging(PutObjectTaggingRequest {
+                bucket: self.opts.path.bucket.clone(),
+                key: key.clone(),
+                tagging: Tagging { tag_set },
+                ..Default::default()
+            })
+            .sync()?;
+
+        Ok(())
+    }
+
+    fn make_public(&self, object: &Object) -> Result<(), Error> {
+        let key = match &object.key {
+            Some(key) => key,
+            None => return Ok(()),
+        };
+
+        self.client
+            .put_object_acl(PutObjectAclRequest {
+                bucket: self.opts.path.bucket.clone(),
+                key: key.clone(),
+                acl: Some("public-read".to_string()),
+                ..Default::default()
+            })
+            .sync()?;
+
+        Ok(())
+    }
+
+    fn copy(
+        &self,
+        object: &Object,
+        destination: &S3path,
+        flat: bool,
+    ) -> Result<(), Error> {
+        let key = match &object.key {
+            Some(key) => key,
+            None => return Ok(()),
+        };
+
+        let target_key = copy_target_key(key, self.opts.path.prefix.as_deref(), destination, flat);
+
+        self.client
+            .copy_object(CopyObjectRequest {
+                bucket: destination.bucket.clone(),
+                key: target_key,
+                copy_source: format!(
+                    "{}/{}",
+                    self.opts.path.bucket,
+                    percent_encode_key(key)
+                ),
+                ..Default::default()
+            })
+            .sync()?;
+
+        Ok(())
+    }
+
+    fn move_object(&self, object: &Object, destination: &S3path) -> Result<(), Error> {
+        self.copy(object, destination, false)?;
+        self.delete(object)
+    }
+
+    fn url_for(&self, key: &str) -> String {
+        format!("s3://{}/{}", self.opts.path.bucket, key)
+    }
+}
+
+/// `x-amz-copy-source` requires the key segment to be percent-encoded;
+/// rusoto does not do this for us, so an unencoded key containing e.g. a
+/// space, `#`, `?`, `+` or non-ASCII character would copy the wrong object
+/// or fail outright. `/` is kept literal since it separates path segments.
+const COPY_SOURCE_KEY_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC.remove(b'/');
+
+fn percent_encode_key(key: &str) -> String {
+    utf8_percent_encode(key, COPY_SOURCE_KEY_ENCODE_SET).to_string()
+}
+
+/// Computes the destination key for `-copy`/`-move`: `flat` drops the
+/// source prefix and places the key directly under the destination prefix,
+/// otherwise the key's path relative to the search prefix is preserved. The
+/// prefix is only stripped at a path boundary (`/` or end-of-string), since
+/// `ListObjectsV2`'s `prefix` matches on a raw string rather than a
+/// directory, so a search prefix of `logs` must not be stripped from a
+/// sibling key like `logs-2024/file.txt`.
+fn copy_target_key(key: &str, source_prefix: Option<&str>, destination: &S3path, flat: bool) -> String {
+    let relative = if flat {
+        Path::new(key)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| key.to_string())
+    } else {
+        match source_prefix {
+            Some(prefix) => match key.strip_prefix(prefix) {
+                Some(rest) if rest.is_empty() || rest.starts_with('/') || prefix.ends_with('/') => {
+                    rest.trim_start_matches('/').to_string()
+                }
+                _ => key.to_string(),
+            },
+            None => key.to_string(),
+        }
+    };
+
+    match &destination.prefix {
+        Some(prefix) if !prefix.is_empty() => format!("{}/{}", prefix, relative),
+        _ => relative,
+    }
+}
+
+fn build_client(opts: &FindOpt) -> S3Client {
+    let region = opts.aws_region.clone().unwrap_or(Region::UsEast1);
+
+    match (&opts.aws_access_key, &opts.aws_secret_key) {
+        (Some(access_key), Some(secret_key)) => {
+            let credentials =
+                StaticProvider::new_minimal(access_key.clone(), secret_key.clone());
+            S3Client::new_with(HttpClient::new().unwrap(), credentials, region)
+        }
+        _ => S3Client::new(region),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit_zero_is_reached_before_the_first_match() {
+        assert!(limit_reached(0, Some(0)));
+    }
+
+    #[test]
+    fn limit_not_yet_reached() {
+        assert!(!limit_reached(2, Some(5)));
+    }
+
+    #[test]
+    fn limit_reached_at_boundary() {
+        assert!(limit_reached(5, Some(5)));
+    }
+
+    #[test]
+    fn no_limit_is_never_reached() {
+        assert!(!limit_reached(1_000_000, None));
+    }
+
+    #[test]
+    fn percent_encode_key_escapes_special_chars_but_not_slashes() {
+        assert_eq!(
+            percent_encode_key("a/b c/d#e?f+g.txt"),
+            "a/b%20c/d%23e%3Ff%2Bg%2Etxt"
+        );
+    }
+
+    #[test]
+    fn safe_join_drops_parent_dir_components() {
+        let target = safe_join("/tmp/dest", "../../../home/user/.ssh/authorized_keys");
+        assert_eq!(
+            target,
+            std::path::PathBuf::from("/tmp/dest/home/user/.ssh/authorized_keys")
+        );
+    }
+
+    #[test]
+    fn safe_join_drops_leading_slash() {
+        let target = safe_join("/tmp/dest", "/etc/passwd");
+        assert_eq!(target, std::path::PathBuf::from("/tmp/dest/etc/passwd"));
+    }
+
+    #[test]
+    fn safe_join_keeps_well_behaved_keys() {
+        let target = safe_join("/tmp/dest", "a/b/file.txt");
+        assert_eq!(target, std::path::PathBuf::from("/tmp/dest/a/b/file.txt"));
+    }
+
+    #[test]
+    fn exec_argv_substitutes_the_placeholder() {
+        let argv = exec_argv("echo {}", "s3://bucket/key").unwrap();
+        assert_eq!(argv, vec!["echo", "s3://bucket/key"]);
+    }
+
+    #[test]
+    fn exec_argv_treats_shell_metacharacters_in_the_key_as_a_plain_argument() {
+        let url = "s3://bucket/$(rm -rf ~); echo pwned";
+        let argv = exec_argv("echo {}", url).unwrap();
+        assert_eq!(argv, vec!["echo", url]);
+    }
+
+    #[test]
+    fn exec_argv_is_none_for_an_empty_utility() {
+        assert!(exec_argv("", "s3://bucket/key").is_none());
+    }
+
+    fn dest(prefix: Option<&str>) -> S3path {
+        S3path {
+            bucket: "dest-bucket".to_string(),
+            prefix: prefix.map(|p| p.to_string()),
+        }
+    }
+
+    #[test]
+    fn flat_drops_the_source_path() {
+        let key = copy_target_key("a/b/c/file.txt", Some("a/b"), &dest(Some("archive")), true);
+        assert_eq!(key, "archive/file.txt");
+    }
+
+    #[test]
+    fn preserves_relative_path_by_default() {
+        let key = copy_target_key("a/b/c/file.txt", Some("a/b"), &dest(Some("archive")), false);
+        assert_eq!(key, "archive/c/file.txt");
+    }
+
+    #[test]
+    fn preserves_full_key_without_source_prefix() {
+        let key = copy_target_key("c/file.txt", None, &dest(Some("archive")), false);
+        assert_eq!(key, "archive/c/file.txt");
+    }
+
+    #[test]
+    fn no_destination_prefix_uses_relative_path_as_is() {
+        let key = copy_target_key("a/b/c/file.txt", Some("a/b"), &dest(None), false);
+        assert_eq!(key, "c/file.txt");
+    }
+
+    #[test]
+    fn prefix_is_not_stripped_from_a_sibling_key_without_a_path_boundary() {
+        let key = copy_target_key(
+            "logs-2024/file.txt",
+            Some("logs"),
+            &dest(Some("archive")),
+            false,
+        );
+        assert_eq!(key, "archive/logs-2024/file.txt");
+    }
+
+    #[test]
+    fn multi_segment_destination_prefix_is_kept_in_full() {
+        let key = copy_target_key(
+            "a/b/c/file.txt",
+            Some("a/b"),
+            &dest(Some("archive/2024/q1")),
+            false,
+        );
+        assert_eq!(key, "archive/2024/q1/c/file.txt");
+    }
+}
@@ -0,0 +1,79 @@
+use rusoto_s3::{Object, Tag};
+use serde::Serialize;
+
+/// A matched object in the shape printed by `--format json`, one per line
+/// so output streams incrementally instead of buffering the whole result
+/// set into a single array.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct FindObject {
+    pub key: Option<String>,
+    pub size: Option<i64>,
+    pub last_modified: Option<String>,
+    pub etag: Option<String>,
+    pub storage_class: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<(String, String)>>,
+}
+
+impl FindObject {
+    pub fn new(object: &Object, tags: Option<&[Tag]>) -> FindObject {
+        FindObject {
+            key: object.key.clone(),
+            size: object.size,
+            last_modified: object.last_modified.clone(),
+            etag: object.e_tag.clone(),
+            storage_class: object.storage_class.clone(),
+            tags: tags.map(|tags| {
+                tags.iter()
+                    .map(|tag| (tag.key.clone(), tag.value.clone()))
+                    .collect()
+            }),
+        }
+    }
+
+    /// Serializes as a single, stable JSON line.
+    pub fn to_json_line(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_json_line_without_tags() {
+        let object = Object {
+            key: Some("path/file.txt".to_string()),
+            size: Some(42),
+            e_tag: Some("\"abc\"".to_string()),
+            storage_class: Some("STANDARD".to_string()),
+            ..Default::default()
+        };
+
+        let line = FindObject::new(&object, None).to_json_line().unwrap();
+
+        assert_eq!(
+            line,
+            r#"{"key":"path/file.txt","size":42,"last_modified":null,"etag":"\"abc\"","storage_class":"STANDARD"}"#
+        );
+    }
+
+    #[test]
+    fn to_json_line_with_tags() {
+        let object = Object {
+            key: Some("path/file.txt".to_string()),
+            ..Default::default()
+        };
+        let tags = vec![Tag {
+            key: "stage".to_string(),
+            value: "tmp".to_string(),
+        }];
+
+        let line = FindObject::new(&object, Some(&tags))
+            .to_json_line()
+            .unwrap();
+
+        assert!(line.contains(r#""tags":[["stage","tmp"]]"#));
+    }
+}
@@ -0,0 +1,73 @@
+use std::ops::Add;
+
+use humansize::{file_size_opts as options, FileSize};
+
+/// Running totals accumulated while folding over the matched objects, used
+/// by `--summarize` to print a single usage line instead of every key.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FindStat {
+    pub count: u64,
+    pub total_size: u64,
+}
+
+impl FindStat {
+    pub fn new(size: u64) -> FindStat {
+        FindStat {
+            count: 1,
+            total_size: size,
+        }
+    }
+}
+
+impl Add for FindStat {
+    type Output = FindStat;
+
+    fn add(self, other: FindStat) -> FindStat {
+        FindStat {
+            count: self.count + other.count,
+            total_size: self.total_size + other.total_size,
+        }
+    }
+}
+
+impl std::fmt::Display for FindStat {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} objects, {}",
+            self.count,
+            self.total_size
+                .file_size(options::BINARY)
+                .unwrap_or_else(|_| "0 B".to_string())
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stat_add() {
+        let a = FindStat::new(100);
+        let b = FindStat::new(200);
+
+        assert_eq!(
+            a + b,
+            FindStat {
+                count: 2,
+                total_size: 300,
+            }
+        );
+    }
+
+    #[test]
+    fn stat_display() {
+        let stat = FindStat {
+            count: 1234,
+            total_size: 5_000_000_000,
+        };
+
+        assert_eq!(stat.to_string(), "1234 objects, 4.66 GiB");
+    }
+}